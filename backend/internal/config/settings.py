@@ -64,6 +64,8 @@ class Settings:
     MOTEC_LDX_EXTENSION: str = os.getenv("MOTEC_LDX_EXTENSION", ".ldx")
     MOTEC_LD_EXTENSION: str = os.getenv("MOTEC_LD_EXTENSION", ".ld")
     MOTEC_LD_HEADER_SIZE: int = int(os.getenv("MOTEC_LD_HEADER_SIZE", "2048"))
+    MOTEC_LD_MAX_CHANNELS: int = int(os.getenv("MOTEC_LD_MAX_CHANNELS", "2048"))
+    MOTEC_GZIP_MAX_SIZE: int = int(os.getenv("MOTEC_GZIP_MAX_SIZE", str(512 * 1024 * 1024)))
     
     # Car Identification Patterns (comma-separated regex patterns)
     CAR_ID_PATTERNS: List[str] = os.getenv(
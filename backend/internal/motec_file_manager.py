@@ -128,6 +128,31 @@ def save_uploaded_file(file_content: bytes, filename: str, file_type: str) -> Di
     return metadata
 
 
+def reindex_files() -> Dict[str, int]:
+    """Re-parse every stored file and refresh its metadata, keeping id and upload time"""
+    all_metadata = load_metadata()
+    by_path = {m.get("file_path"): m for m in all_metadata}
+    
+    scanned = MotecParser.scan_directory(MOTEC_FILES_DIR, recursive=True)
+    updated = 0
+    for result in scanned:
+        rel_path = str(Path(result["file_path"]).relative_to(BASE_DIR))
+        entry = by_path.get(rel_path)
+        if entry is None:
+            # Not an upload (e.g. a generated export) - leave it out of the index
+            continue
+        
+        kept = {key: entry[key] for key in ("id", "uploaded_at") if key in entry}
+        entry.clear()
+        entry.update(result)
+        entry.update(kept)
+        entry["file_path"] = rel_path
+        updated += 1
+    
+    save_metadata(all_metadata)
+    return {"scanned": len(scanned), "updated": updated}
+
+
 def get_all_files() -> List[Dict[str, Any]]:
     """Get all uploaded MoTeC files"""
     return load_metadata()
@@ -4,11 +4,12 @@ MoTeC LDX Updater - Updates specific parameter values in existing LDX files
 import xml.etree.ElementTree as ET
 from pathlib import Path
 from typing import Optional, Dict, Any
+import gzip
 import hashlib
 import os
 import time
 from datetime import datetime
-from .motec_parser import MotecParser
+from .motec_parser import MotecParser, MotecLdxParser, is_gzip_file
 
 
 class MotecLdxUpdater:
@@ -81,8 +82,8 @@ class MotecLdxUpdater:
                 print(f"[LDX_UPDATER] ERROR: File does not exist: {file_path}")
                 return False
             
-            # Parse the XML
-            tree = ET.parse(file_path)
+            # Parse the XML (keeping comments so hand annotations survive the rewrite)
+            tree, top_level_comments = MotecLdxParser.load_tree(file_path)
             root = tree.getroot()
             
             # Get original content hash for comparison
@@ -161,7 +162,10 @@ class MotecLdxUpdater:
                 
                 # Write XML with proper formatting
                 with open(temp_path, 'wb') as f:
-                    xml_bytes = ET.tostring(root, encoding='utf-8', xml_declaration=True)
+                    xml_bytes = MotecLdxParser.serialize_tree(root, top_level_comments)
+                    if is_gzip_file(file_path):
+                        # Keep .ldx.gz archives compressed
+                        xml_bytes = gzip.compress(xml_bytes)
                     f.write(xml_bytes)
                     f.flush()
                     os.fsync(f.fileno())  # Force write to disk
@@ -181,7 +185,7 @@ class MotecLdxUpdater:
                 
                 # Verify the change is actually in the file
                 try:
-                    verify_tree = ET.parse(file_path)
+                    verify_tree, _ = MotecLdxParser.load_tree(file_path)
                     verify_root = verify_tree.getroot()
                     
                     # Check if our change is actually there
@@ -400,7 +404,7 @@ class MotecLdxUpdater:
             if not file_path.exists():
                 return False
             
-            tree = ET.parse(file_path)
+            tree, _ = MotecLdxParser.load_tree(file_path)
             root = tree.getroot()
             
             # Check based on parameter type
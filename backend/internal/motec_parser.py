@@ -4,23 +4,277 @@ Handles XML-based LDX files and binary LD files
 """
 import struct
 import re
+import gzip
+import shutil
+import tempfile
+import threading
+from contextlib import contextmanager
 from pathlib import Path
 from datetime import datetime
-from typing import Dict, Any, List, Optional
+from typing import Dict, Any, List, Optional, Tuple
 import xml.etree.ElementTree as ET
 from .config.settings import settings
 
+# MoTeC LD files begin with the little-endian u32 format marker 0x40
+LD_MAGIC = b"\x40\x00\x00\x00"
+
+# Archived sessions may be stored gzip-compressed (.ld.gz / .ldx.gz)
+GZIP_MAGIC = b"\x1f\x8b"
+
+# Size of the fixed LD header that holds the session and device fields
+LD_MIN_HEADER_SIZE = 0x6E2
+
+# Size of one channel meta record in the linked list the header points to
+LD_CHANNEL_META_SIZE = 124
+
+# Size of the event block (event name, session, long comment) the header points to
+LD_EVENT_BLOCK_SIZE = 0x480
+
+
+# Guards ElementTree's process-wide prefix map while an LDX file is serialized
+_NAMESPACE_MAP_LOCK = threading.Lock()
+
+
+class LdFileTooSmallError(ValueError):
+    """LD file is shorter than its fixed header (usually a truncated upload)"""
+    
+    def __init__(self, needed: int, got: int):
+        self.needed = needed
+        self.got = got
+        super().__init__(f"LD file too small: need at least {needed} bytes, got {got}")
+
+
+class LdBadSignatureError(ValueError):
+    """File does not start with the LD format marker (not a MoTeC log)"""
+    
+    def __init__(self, expected: bytes, found: bytes):
+        self.expected = expected
+        self.found = found
+        super().__init__(f"Bad LD signature: expected {expected.hex()}, found {found.hex()}")
+
+
+class LdTooManyChannelsError(ValueError):
+    """LD file declares more channels than MOTEC_LD_MAX_CHANNELS allows"""
+    
+    def __init__(self, limit: int, actual: int):
+        self.limit = limit
+        self.actual = actual
+        super().__init__(f"LD file declares {actual} channels, more than the limit of {limit}")
+
+
+class GzipTooLargeError(ValueError):
+    """Gzip-compressed MoTeC file expands past MOTEC_GZIP_MAX_SIZE"""
+    
+    def __init__(self, limit: int):
+        self.limit = limit
+        super().__init__(f"Decompressed file exceeds the {limit} byte limit")
+
+
+class _CappedReader:
+    """File wrapper that raises GzipTooLargeError once more than limit bytes are read"""
+    
+    def __init__(self, raw, limit: int):
+        self._raw = raw
+        self._limit = limit
+        self._total = 0
+    
+    def read(self, size: int = -1) -> bytes:
+        # Never ask for more than one byte past the limit, even for read()
+        remaining = self._limit - self._total + 1
+        if size is None or size < 0 or size > remaining:
+            size = remaining
+        data = self._raw.read(size)
+        self._total += len(data)
+        if self._total > self._limit:
+            raise GzipTooLargeError(self._limit)
+        return data
+    
+    def close(self) -> None:
+        self._raw.close()
+    
+    def __enter__(self):
+        return self
+    
+    def __exit__(self, *exc) -> None:
+        self.close()
+
+
+def is_gzip_file(file_path: Path) -> bool:
+    """True for a .gz-named file whose content starts with the gzip magic"""
+    file_path = Path(file_path)
+    if not file_path.name.lower().endswith(".gz"):
+        return False
+    with open(file_path, 'rb') as f:
+        return f.read(len(GZIP_MAGIC)) == GZIP_MAGIC
+
+
+def open_motec_file(file_path: Path):
+    """
+    Open a MoTeC file for binary reading
+    
+    .gz archives are decompressed on the fly, up to MOTEC_GZIP_MAX_SIZE bytes.
+    Other names are opened as-is, so an upload called x.ld is never inflated.
+    """
+    if is_gzip_file(file_path):
+        return _CappedReader(gzip.open(file_path, 'rb'), settings.MOTEC_GZIP_MAX_SIZE)
+    return open(file_path, 'rb')
+
+
+@contextmanager
+def decompressed_copy(file_path: Path):
+    """
+    Yield a path to the uncompressed file
+    
+    Plain files are yielded unchanged. .gz archives are inflated (within
+    MOTEC_GZIP_MAX_SIZE) into a temp file that keeps the inner name, e.g.
+    session.ld.gz becomes session.ld, and is removed afterwards.
+    """
+    file_path = Path(file_path)
+    if not is_gzip_file(file_path):
+        yield file_path
+        return
+    
+    with tempfile.TemporaryDirectory() as tmp_dir:
+        inner_path = Path(tmp_dir) / file_path.name[:-3]
+        with open_motec_file(file_path) as src, open(inner_path, 'wb') as dst:
+            shutil.copyfileobj(src, dst)
+        yield inner_path
+
 
 class MotecLdxParser:
     """Parser for MoTeC LDX (XML-based workspace) files"""
     
+    # Root element names written by different i2 versions (matched case-insensitively)
+    KNOWN_ROOT_TAGS = ("LDXFile", "Workspace")
+    
+    @staticmethod
+    def _strip_namespaces(root: ET.Element) -> List[Tuple[ET.Element, str, Dict[str, str]]]:
+        """
+        Drop XML namespace URIs from element tags and attribute names in place
+        
+        Returns:
+            (element, original tag, {local attribute: original attribute}) for
+            every element that was renamed, so _restore_namespaces can undo it
+        """
+        renamed = []
+        for elem in root.iter():
+            if not isinstance(elem.tag, str):
+                continue
+            original_tag = elem.tag
+            if elem.tag.startswith("{"):
+                elem.tag = elem.tag.split("}", 1)[1]
+            original_attrs = {}
+            for name in [n for n in elem.attrib if n.startswith("{")]:
+                local = name.split("}", 1)[1]
+                if local in elem.attrib:
+                    # A plain attribute of the same name wins; keep this one namespaced
+                    continue
+                elem.attrib[local] = elem.attrib.pop(name)
+                original_attrs[local] = name
+            if original_tag != elem.tag or original_attrs:
+                renamed.append((elem, original_tag, original_attrs))
+        return renamed
+    
+    @staticmethod
+    def _restore_namespaces(renamed: List[Tuple[ET.Element, str, Dict[str, str]]]) -> None:
+        """Put back the namespaced names recorded by _strip_namespaces"""
+        for elem, original_tag, original_attrs in renamed:
+            elem.tag = original_tag
+            for local, name in original_attrs.items():
+                if local in elem.attrib:
+                    elem.attrib[name] = elem.attrib.pop(local)
+    
+    @staticmethod
+    def load_tree(file_path: Path) -> Tuple[ET.ElementTree, Dict[str, List[str]]]:
+        """
+        Parse an LDX file keeping its XML comments, with namespaces stripped
+        
+        Comments inside the root element stay in the tree. Comments outside it
+        (which ElementTree cannot hold) are returned separately so they can be
+        written back with serialize_tree. Namespaced files get plain tag and
+        attribute names so .//Details style lookups work; serialize_tree puts
+        the namespaces back.
+        
+        Returns:
+            (tree, {"before_root": [...], "after_root": [...],
+                    "namespaces": {prefix: uri}, "renamed": [...]})
+        """
+        parser = ET.XMLParser(target=ET.TreeBuilder(insert_comments=True))
+        with open_motec_file(file_path) as f:
+            tree = ET.parse(f, parser=parser)
+        
+        comments = {"before_root": [], "after_root": [], "namespaces": {}}
+        depth = 0
+        seen_root = False
+        events = ("start", "end", "comment", "start-ns")
+        with open_motec_file(file_path) as f:
+            for event, elem in ET.iterparse(f, events=events):
+                if event == "start":
+                    depth += 1
+                    seen_root = True
+                elif event == "end":
+                    depth -= 1
+                elif event == "start-ns":
+                    prefix, uri = elem
+                    comments["namespaces"].setdefault(prefix, uri)
+                elif depth == 0:
+                    key = "after_root" if seen_root else "before_root"
+                    comments[key].append(elem.text or "")
+        
+        comments["renamed"] = MotecLdxParser._strip_namespaces(tree.getroot())
+        return tree, comments
+    
+    @staticmethod
+    def _tostring_with_prefixes(root: ET.Element, namespaces: Dict[str, str]) -> bytes:
+        """Serialize using the file's own namespace prefixes without leaving them registered"""
+        # ElementTree only takes prefixes from its global map, so register them for this call only
+        with _NAMESPACE_MAP_LOCK:
+            saved = dict(ET._namespace_map)
+            try:
+                for prefix, uri in namespaces.items():
+                    try:
+                        ET.register_namespace(prefix, uri)
+                    except ValueError:
+                        # Reserved ns0-style prefixes are regenerated by ElementTree
+                        pass
+                return ET.tostring(root, encoding="utf-8")
+            finally:
+                ET._namespace_map.clear()
+                ET._namespace_map.update(saved)
+    
+    @staticmethod
+    def serialize_tree(root: ET.Element, comments: Optional[Dict[str, List[str]]] = None) -> bytes:
+        """Serialize an LDX root element with its top-level comments and namespaces restored"""
+        comments = comments or {}
+        parts = [b"<?xml version='1.0' encoding='utf-8'?>\n"]
+        for text in comments.get("before_root", []):
+            parts.append(f"<!--{text}-->\n".encode("utf-8"))
+        
+        renamed = comments.get("renamed", [])
+        MotecLdxParser._restore_namespaces(renamed)
+        try:
+            parts.append(MotecLdxParser._tostring_with_prefixes(root, comments.get("namespaces", {})))
+        finally:
+            # Leave the caller's tree with the plain names it was working on
+            MotecLdxParser._strip_namespaces(root)
+        for text in comments.get("after_root", []):
+            parts.append(f"\n<!--{text}-->".encode("utf-8"))
+        return b"".join(parts)
+    
     @staticmethod
     def parse(file_path: Path) -> Dict[str, Any]:
         """Parse an LDX file and extract all available information"""
         try:
-            tree = ET.parse(file_path)
+            tree, top_level_comments = MotecLdxParser.load_tree(file_path)
             root = tree.getroot()
             
+            known_roots = MotecLdxParser.KNOWN_ROOT_TAGS
+            if root.tag.lower() not in (tag.lower() for tag in known_roots):
+                raise ValueError(
+                    f"Unrecognised LDX root element <{root.tag}>, expected one of: "
+                    + ", ".join(f"<{tag}>" for tag in known_roots)
+                )
+            
             result = {
                 "file_type": "ldx",
                 "filename": file_path.name,
@@ -81,6 +335,20 @@ class MotecLdxParser:
             if range_blocks:
                 result["has_range_block"] = True
             
+            # Distinct units used by math items and channel descriptors
+            units = {m.get("Unit", "") for m in root.findall(".//MathScaleOffset")}
+            units |= {d.get("DisplayUnit", "") for d in root.findall(".//Descriptor")}
+            units.discard("")
+            if units:
+                result["channel_units"] = sorted(units)
+            
+            # Keep human annotations (top-level and in-document comments)
+            comments = top_level_comments["before_root"] + [
+                c.text or "" for c in root.iter(ET.Comment)
+            ] + top_level_comments["after_root"]
+            if comments:
+                result["comments"] = comments
+            
             return result
             
         except Exception as e:
@@ -123,6 +391,218 @@ class MotecLdParser:
         
         return strings
     
+    @staticmethod
+    def _read_fixed_string(data: bytes, offset: int, length: int) -> Optional[str]:
+        """Read a null-terminated fixed-width string field, None if empty or out of range"""
+        if offset + length > len(data):
+            return None
+        raw = data[offset:offset + length].split(b'\x00', 1)[0]
+        value = raw.decode('ascii', errors='ignore').strip()
+        return value or None
+    
+    @staticmethod
+    def _parse_device_info(header: bytes) -> Dict[str, Any]:
+        """Read logger device serial, type and firmware version from their header offsets"""
+        info = {}
+        
+        # Layout: u32 serial @ 0x46, 8-byte device type @ 0x4A, u16 firmware version @ 0x52
+        if len(header) < 0x54:
+            return info
+        
+        serial = struct.unpack_from('<I', header, 0x46)[0]
+        if serial:
+            info["device_serial"] = str(serial)
+        
+        device_type = MotecLdParser._read_fixed_string(header, 0x4A, 8)
+        if device_type:
+            info["device_type"] = device_type
+        
+        firmware = struct.unpack_from('<H', header, 0x52)[0]
+        if firmware:
+            info["firmware_version"] = str(firmware)
+        
+        return info
+    
+    @staticmethod
+    def _parse_session_fields(header: bytes) -> Dict[str, Any]:
+        """Read driver, vehicle, venue and short comment from their header offsets"""
+        info = {}
+        
+        # Layout: 64-byte strings for driver @ 0x9E, vehicle @ 0xDE, venue @ 0x15E, short comment @ 0x624
+        fields = {
+            "driver_name": (0x9E, 64),
+            "vehicle_id": (0xDE, 64),
+            "track_name": (0x15E, 64),
+            "comment": (0x624, 64),
+        }
+        for key, (offset, length) in fields.items():
+            value = MotecLdParser._read_fixed_string(header, offset, length)
+            if value:
+                info[key] = value
+        
+        return info
+    
+    @staticmethod
+    def _parse_start_time(header: bytes) -> Dict[str, Any]:
+        """Read the session date/time fields and combine them into an ISO start time"""
+        info = {}
+        
+        # Layout: 16-byte ASCII date (DD/MM/YYYY) @ 0x5E, 16-byte ASCII time (HH:MM:SS) @ 0x7E
+        date_str = MotecLdParser._read_fixed_string(header, 0x5E, 16)
+        time_str = MotecLdParser._read_fixed_string(header, 0x7E, 16)
+        
+        # Anything other than digits and separators is garbage, not a date or time
+        if date_str and not re.fullmatch(r'[\d/.\-]+', date_str):
+            date_str = None
+        if time_str and not re.fullmatch(r'[\d:.]+', time_str):
+            time_str = None
+        if date_str:
+            info["date"] = date_str
+        if time_str:
+            info["time"] = time_str
+        
+        # Blank fields stay absent rather than turning into an epoch date
+        if date_str and time_str:
+            for fmt in ("%d/%m/%Y %H:%M:%S", "%d/%m/%Y %H:%M"):
+                try:
+                    info["start_time"] = datetime.strptime(f"{date_str} {time_str}", fmt).isoformat()
+                    break
+                except ValueError:
+                    continue
+        
+        return info
+    
+    @staticmethod
+    def _parse_event_block(block: bytes) -> Dict[str, Any]:
+        """Read event name, session and long comment from the event block"""
+        info = {}
+        
+        # Layout: 64-byte event name, 64-byte session, 1024-byte comment
+        fields = {
+            "event_name": (0, 64),
+            "session_name": (64, 64),
+            "event_comment": (128, 1024),
+        }
+        for key, (offset, length) in fields.items():
+            value = MotecLdParser._read_fixed_string(block, offset, length)
+            if value:
+                info[key] = value
+        
+        return info
+    
+    @staticmethod
+    def _describe_data_type(type_code: int, width: int) -> str:
+        """Name a channel's stored sample type from its type code and byte width"""
+        if type_code == 0x07:
+            kind = "float"
+        elif type_code in (0x00, 0x03, 0x05):
+            kind = "int"
+        else:
+            return f"unknown({type_code:#x})"
+        return f"{kind}{width * 8}"
+    
+    @staticmethod
+    def _parse_channel_meta(f, meta_ptr: int, file_size: int, max_channels: int) -> Dict[str, Any]:
+        """
+        Walk the linked list of channel meta records without reading sample data
+        
+        Each record is: u32 prev/next/data pointers, u32 sample count, u16 counter,
+        u16 type code, u16 byte width, u16 rate (Hz), 4 x i16 scaling, then
+        32-byte name @ 0x20, 8-byte short name @ 0x40 and 12-byte units @ 0x48.
+        """
+        channels = []
+        visited = set()
+        ptr = meta_ptr
+        data_end = 0
+        meta_cut_off = False
+        while ptr and ptr not in visited and len(channels) < max_channels:
+            if ptr + LD_CHANNEL_META_SIZE > file_size:
+                meta_cut_off = True
+                break
+            visited.add(ptr)
+            f.seek(ptr)
+            record = f.read(LD_CHANNEL_META_SIZE)
+            
+            next_ptr, data_ptr, sample_count = struct.unpack_from('<III', record, 0x04)
+            type_code, width, rate = struct.unpack_from('<HHH', record, 0x12)
+            data_end = max(data_end, data_ptr + sample_count * width)
+            channels.append({
+                "name": MotecLdParser._read_fixed_string(record, 0x20, 32) or "",
+                "short_name": MotecLdParser._read_fixed_string(record, 0x40, 8) or "",
+                "units": MotecLdParser._read_fixed_string(record, 0x48, 12) or "",
+                "sample_rate": rate,
+                "sample_count": sample_count,
+                "data_type": MotecLdParser._describe_data_type(type_code, width),
+                "data_size": sample_count * width,
+            })
+            ptr = next_ptr
+        
+        info = {
+            "channel_count": len(channels),
+            "channels": channels,
+            # Every meta record and sample block the header declares is in the file
+            "complete": not meta_cut_off and data_end <= file_size,
+        }
+        if channels:
+            # False means tools must handle per-channel rates, not one flat sample clock
+            info["consistent_rate"] = len({c["sample_rate"] for c in channels}) == 1
+            units = {c["units"] for c in channels if c["units"]}
+            if units:
+                # Distinct units, for spotting "kph" vs "km/h" across files
+                info["channel_units"] = sorted(units)
+        if len(channels) >= max_channels and ptr and ptr not in visited:
+            info["channels_truncated"] = True
+            
+            # Count the rest of the list (pointers only, no records kept) to report the real total
+            declared = len(channels)
+            while ptr and ptr not in visited and ptr + LD_CHANNEL_META_SIZE <= file_size:
+                visited.add(ptr)
+                f.seek(ptr + 0x04)
+                ptr = struct.unpack('<I', f.read(4))[0]
+                declared += 1
+            info["declared_channel_count"] = declared
+        return info
+    
+    @staticmethod
+    def channels_by_rate(channels: List[Dict[str, Any]]) -> List[Tuple[str, int]]:
+        """(name, sample rate) for parsed channels, highest rate first"""
+        pairs = [(c["name"], c["sample_rate"]) for c in channels]
+        return sorted(pairs, key=lambda pair: pair[1], reverse=True)
+    
+    @staticmethod
+    def size_breakdown(channels: List[Dict[str, Any]]) -> List[Tuple[str, int]]:
+        """(name, sample bytes) for parsed channels, largest first"""
+        pairs = [(c["name"], c["data_size"]) for c in channels]
+        return sorted(pairs, key=lambda pair: pair[1], reverse=True)
+    
+    @staticmethod
+    def is_complete(file_path: Path) -> bool:
+        """
+        Check whether an LD file holds all the data its header declares
+        
+        Compares the end of each channel's sample block (data pointer plus
+        sample count times width) with the file length, so a server can reject
+        a partial upload before a full parse. A short file returns False rather
+        than raising. .ld.gz archives are checked on their decompressed contents.
+        
+        Raises:
+            LdBadSignatureError: If the file is not an LD log
+        """
+        with decompressed_copy(file_path) as ld_path:
+            file_size = ld_path.stat().st_size
+            with open(ld_path, 'rb') as f:
+                header = f.read(LD_MIN_HEADER_SIZE)
+                if len(header) >= len(LD_MAGIC) and not header.startswith(LD_MAGIC):
+                    raise LdBadSignatureError(LD_MAGIC, header[:len(LD_MAGIC)])
+                if file_size < LD_MIN_HEADER_SIZE:
+                    return False
+                
+                meta_ptr = struct.unpack_from('<I', header, 0x08)[0]
+                info = MotecLdParser._parse_channel_meta(
+                    f, meta_ptr, file_size, settings.MOTEC_LD_MAX_CHANNELS
+                )
+        return info["complete"]
+    
     @staticmethod
     def _parse_date_time(text: str) -> Optional[Dict[str, str]]:
         """Try to extract date and time from text"""
@@ -185,7 +665,13 @@ class MotecLdParser:
     
     @staticmethod
     def parse(file_path: Path, header_size: Optional[int] = None) -> Dict[str, Any]:
-        """Parse an LD file and extract metadata from header"""
+        """
+        Parse an LD file and extract metadata from header
+        
+        If a read fails part-way, the fields read so far are kept alongside
+        parse_error and error_type.
+        """
+        result = {}
         try:
             stat = file_path.stat()
             result = {
@@ -195,13 +681,21 @@ class MotecLdParser:
                 "parsed_at": datetime.now().isoformat(),
             }
             
-            # Use configured header size
-            ld_header_size = header_size or settings.MOTEC_LD_HEADER_SIZE
+            # Use configured header size, but never less than the fixed header fields
+            ld_header_size = max(header_size or settings.MOTEC_LD_HEADER_SIZE, LD_MIN_HEADER_SIZE)
             
             with open(file_path, 'rb') as f:
                 # Read header section
                 header = f.read(ld_header_size)
                 
+                # Reject files that are not LD logs, or are cut off mid-header
+                if len(header) < len(LD_MAGIC):
+                    raise LdFileTooSmallError(LD_MIN_HEADER_SIZE, stat.st_size)
+                if not header.startswith(LD_MAGIC):
+                    raise LdBadSignatureError(LD_MAGIC, header[:len(LD_MAGIC)])
+                if stat.st_size < LD_MIN_HEADER_SIZE:
+                    raise LdFileTooSmallError(LD_MIN_HEADER_SIZE, stat.st_size)
+                
                 # Extract all readable strings from header
                 strings = MotecLdParser._extract_strings(header, min_length=3)
                 
@@ -209,31 +703,44 @@ class MotecLdParser:
                 session_info = MotecLdParser._extract_session_info(strings)
                 result.update(session_info)
                 
+                # Logger device details stored at fixed offsets
+                result.update(MotecLdParser._parse_device_info(header))
+                
+                # Session strings override the guesses made from loose strings
+                result.update(MotecLdParser._parse_session_fields(header))
+                result.update(MotecLdParser._parse_start_time(header))
+                
+                # Event details live in a separate block pointed to from the header
+                if len(header) >= 0x28:
+                    event_ptr = struct.unpack_from('<I', header, 0x24)[0]
+                    if 0 < event_ptr < stat.st_size:
+                        f.seek(event_ptr)
+                        result.update(MotecLdParser._parse_event_block(f.read(LD_EVENT_BLOCK_SIZE)))
+                
+                # Channel definitions (header-only, sample blocks are not read)
+                if len(header) >= 0x0C:
+                    meta_ptr = struct.unpack_from('<I', header, 0x08)[0]
+                    result.update(MotecLdParser._parse_channel_meta(
+                        f, meta_ptr, stat.st_size, settings.MOTEC_LD_MAX_CHANNELS
+                    ))
+                
                 # Store raw strings for reference (limited to avoid huge output)
                 result["extracted_strings"] = strings[:50]  # Limit to first 50 strings
                 
-                # Try to parse structured header (if we know the format)
-                # MoTeC LD files typically have:
-                # - File signature/version at offset 0
-                # - Metadata strings at various offsets
-                # - Channel definitions after header
-                
-                # Read first few bytes as potential file signature
-                f.seek(0)
-                signature = f.read(4)
-                if len(signature) >= 4:
-                    # Try to interpret as integers
-                    try:
-                        # Could be version info or file type marker
-                        vals = struct.unpack('<4B', signature)
-                        result["header_signature"] = [hex(v) for v in vals]
-                    except:
-                        pass
+                # Format marker bytes (already checked against LD_MAGIC above)
+                result["header_signature"] = [hex(v) for v in header[:len(LD_MAGIC)]]
+            
+            # Report the cap as an error; the channels read so far are kept with it
+            if result.get("channels_truncated"):
+                raise LdTooManyChannelsError(
+                    settings.MOTEC_LD_MAX_CHANNELS, result["declared_channel_count"]
+                )
             
             return result
             
         except Exception as e:
             return {
+                **result,
                 "file_type": "ld",
                 "filename": file_path.name,
                 "file_size": file_path.stat().st_size if file_path.exists() else 0,
@@ -254,13 +761,65 @@ class MotecParser:
         if not file_path.exists():
             raise FileNotFoundError(f"File not found: {file_path}")
         
+        if is_gzip_file(file_path):
+            return MotecParser._parse_gzip(file_path)
+        
         if file_path.suffix.lower() == settings.MOTEC_LDX_EXTENSION.lower():
             return MotecLdxParser.parse(file_path)
         elif file_path.suffix.lower() == settings.MOTEC_LD_EXTENSION.lower():
             return MotecLdParser.parse(file_path)
+        
+        # Unrecognised extension - fall back to the file contents
+        detected = MotecParser.detect_file_type(file_path)
+        if detected == "ldx":
+            return MotecLdxParser.parse(file_path)
+        elif detected == "ld":
+            return MotecLdParser.parse(file_path)
         else:
             raise ValueError(f"Unsupported file type: {file_path.suffix}")
     
+    @staticmethod
+    def _parse_gzip(file_path: Path) -> Dict[str, Any]:
+        """Parse a .gz-compressed MoTeC file through a size-capped temp copy"""
+        with decompressed_copy(file_path) as inner_path:
+            result = MotecParser.parse_file(inner_path)
+        
+        result["filename"] = file_path.name
+        result["compressed_size"] = file_path.stat().st_size
+        return result
+    
+    @staticmethod
+    def detect_file_type(file_path: Path) -> str:
+        """
+        Detect a MoTeC file's type from its leading bytes
+        
+        .gz archives are detected by the type of their contents.
+        
+        Returns:
+            "ld" if the file starts with the LD format marker, "ldx" if it
+            starts like an XML document, otherwise "unknown"
+        """
+        with open(file_path, 'rb') as f:
+            head = f.read(64)
+        
+        if head.startswith(GZIP_MAGIC) and is_gzip_file(file_path):
+            try:
+                with open_motec_file(file_path) as f:
+                    head = f.read(64)
+            except (OSError, EOFError):
+                return "unknown"
+        
+        if head.startswith(LD_MAGIC):
+            return "ld"
+        
+        # Skip a UTF-8 byte order mark and leading whitespace before the XML
+        if head.startswith(b"\xef\xbb\xbf"):
+            head = head[3:]
+        if head.lstrip().startswith(b"<"):
+            return "ldx"
+        
+        return "unknown"
+    
     @staticmethod
     def parse_metadata(file_path: Path) -> Dict[str, Any]:
         """Parse file and return simplified metadata for storage"""
@@ -273,15 +832,21 @@ class MotecParser:
             "file_size": full_parse.get("file_size", 0),
             "parsed_at": full_parse.get("parsed_at", datetime.now().isoformat()),
         }
+        if "compressed_size" in full_parse:
+            metadata["compressed_size"] = full_parse["compressed_size"]
         
         # LDX-specific metadata
         if full_parse.get("file_type") == "ldx":
+            if full_parse.get("version"):
+                metadata["ldx_version"] = full_parse["version"]
             if "details" in full_parse:
                 metadata.update(full_parse["details"])
             if "total_markers" in full_parse:
                 metadata["total_markers"] = full_parse["total_markers"]
             if "marker_groups" in full_parse:
                 metadata["marker_group_count"] = len(full_parse["marker_groups"])
+            if "channel_units" in full_parse:
+                metadata["channel_units"] = full_parse["channel_units"]
         
         # LD-specific metadata
         elif full_parse.get("file_type") == "ld":
@@ -289,16 +854,76 @@ class MotecParser:
                 metadata["date"] = full_parse["date"]
             if "time" in full_parse:
                 metadata["time"] = full_parse["time"]
+            if "start_time" in full_parse:
+                metadata["start_time"] = full_parse["start_time"]
             if "driver_name" in full_parse:
                 metadata["driver_name"] = full_parse["driver_name"]
             if "device_name" in full_parse:
                 metadata["device_name"] = full_parse["device_name"]
             if "track_name" in full_parse:
                 metadata["track_name"] = full_parse["track_name"]
+            for key in ("vehicle_id", "event_name", "session_name", "comment", "event_comment",
+                        "device_serial", "device_type", "firmware_version",
+                        "channel_count", "channels", "channels_truncated",
+                        "declared_channel_count", "complete", "consistent_rate",
+                        "channel_units"):
+                if key in full_parse:
+                    metadata[key] = full_parse[key]
         
         # Add any parse errors
         if "parse_error" in full_parse:
             metadata["parse_error"] = full_parse["parse_error"]
+            if "error_type" in full_parse:
+                metadata["error_type"] = full_parse["error_type"]
         
         return metadata
-
+    
+    @staticmethod
+    def _motec_suffix(file_path: Path) -> str:
+        """File extension, looking past a trailing .gz (session.ld.gz -> .ld)"""
+        suffixes = [x.lower() for x in file_path.suffixes]
+        if len(suffixes) >= 2 and suffixes[-1] == ".gz":
+            return suffixes[-2]
+        return suffixes[-1] if suffixes else ""
+    
+    @staticmethod
+    def scan_directory(directory: Path, recursive: bool = False) -> List[Dict[str, Any]]:
+        """
+        Parse metadata for every MoTeC file in a directory
+        
+        Args:
+            directory: Directory to scan
+            recursive: Also scan subdirectories
+        
+        Returns:
+            One metadata dict per file, sorted by path. Files that fail to parse
+            get an entry with parse_error set instead of aborting the scan.
+        """
+        directory = Path(directory)
+        if not directory.is_dir():
+            raise NotADirectoryError(f"Not a directory: {directory}")
+        
+        extensions = {
+            settings.MOTEC_LDX_EXTENSION.lower(),
+            settings.MOTEC_LD_EXTENSION.lower(),
+        }
+        pattern = "**/*" if recursive else "*"
+        paths = sorted(
+            p for p in directory.glob(pattern)
+            if p.is_file() and MotecParser._motec_suffix(p) in extensions
+        )
+        
+        results = []
+        for path in paths:
+            try:
+                metadata = MotecParser.parse_metadata(path)
+            except Exception as e:
+                metadata = {
+                    "filename": path.name,
+                    "parse_error": str(e),
+                    "error_type": type(e).__name__
+                }
+            metadata["file_path"] = str(path)
+            results.append(metadata)
+        
+        return results
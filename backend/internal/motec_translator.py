@@ -7,6 +7,7 @@ from pathlib import Path
 from datetime import datetime
 from typing import Dict, Any, List, Optional
 from .motec_parser import MotecParser, MotecLdxParser
+from .config.settings import settings
 
 
 class MotecTranslator:
@@ -61,6 +62,7 @@ class MotecTranslator:
             
             parameters = []
             now = datetime.now().isoformat()
+            param_subteam = subteam or settings.MOTEC_DEFAULT_SUBTEAM
             
             # Convert Details String elements to parameters
             if include_details and "details" in ldx_data:
@@ -81,7 +83,7 @@ class MotecTranslator:
             # Extract MathItems if available (need to parse XML directly for now)
             if include_math_items:
                 try:
-                    tree = ET.parse(file_path)
+                    tree, _ = MotecLdxParser.load_tree(file_path)
                     root = tree.getroot()
                     
                     # Find MathItems
@@ -128,7 +130,7 @@ class MotecTranslator:
             
             # Extract Descriptors if available
             try:
-                tree = ET.parse(file_path)
+                tree, _ = MotecLdxParser.load_tree(file_path)
                 root = tree.getroot()
                 
                 descriptors = root.findall(".//Descriptors/Descriptor")
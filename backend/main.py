@@ -51,9 +51,10 @@ from internal.motec_file_manager import (
     get_all_files,
     get_file_by_id,
     delete_file as delete_motec_file,
-    get_file_path
+    get_file_path,
+    reindex_files as reindex_motec_files
 )
-from internal.motec_parser import MotecParser
+from internal.motec_parser import MotecParser, MotecLdParser
 from internal.motec_translator import MotecTranslator
 from internal.motec_ldx_updater import MotecLdxUpdater
 from internal.car_parameters import (
@@ -1099,6 +1100,18 @@ async def api_upload_motec_file(
             }
         }
         
+        # Partial LD uploads are kept, but flagged so the user knows to re-upload
+        if file_type == "ld" and file_path and file_path.exists():
+            try:
+                if not MotecLdParser.is_complete(file_path):
+                    response["warning"] = (
+                        f"{filename} looks incomplete: its channel data runs past the end of the file. "
+                        "The upload may have been cut off."
+                    )
+            except ValueError:
+                # Not an LD log; the parse error is already in the file metadata
+                pass
+        
         # Add queue application results if auto-injection occurred
         if queue_application_result:
             response["queue_application"] = {
@@ -1141,7 +1154,11 @@ async def api_get_motec_files(request: Request):
     """Get all uploaded MoTeC files"""
     require_auth(request)
     try:
-        files = get_all_files()
+        # Per-channel lists can be thousands of entries; GET /api/motec/files/{file_id} serves them
+        files = [
+            {key: value for key, value in file_meta.items() if key != "channels"}
+            for file_meta in get_all_files()
+        ]
         # Sort by uploaded_at, newest first
         # Handle cases where uploaded_at might be missing or invalid
         files.sort(key=lambda x: x.get("uploaded_at", "") or "", reverse=True)
@@ -1154,6 +1171,17 @@ async def api_get_motec_files(request: Request):
         raise HTTPException(status_code=500, detail=error_msg)
 
 
+@app.post("/api/motec/files/reindex")
+async def api_reindex_motec_files(request: Request):
+    """Re-parse all stored MoTeC files so their metadata picks up parser changes"""
+    await require_role(request, settings.ROLE_ADMIN)
+    try:
+        counts = reindex_motec_files()
+        return {"status": "success", **counts}
+    except Exception as e:
+        raise HTTPException(status_code=500, detail=f"Error reindexing MoTeC files: {str(e)}")
+
+
 @app.get("/api/motec/files/{file_id}")
 async def api_get_motec_file(request: Request, file_id: str):
     """Get file metadata by ID"""
@@ -1661,13 +1661,20 @@ async function loadMotecFiles() {
             
             // LD file details
             if (file.file_type === 'ld') {
-                if (file.date) details.push(`Date: ${file.date}`);
-                if (file.time) details.push(`Time: ${file.time}`);
-                if (file.driver_name) details.push(`Driver: ${file.driver_name}`);
-                if (file.device_name) details.push(`Device: ${file.device_name}`);
-                if (file.track_name) details.push(`Track: ${file.track_name}`);
+                if (file.date) details.push(`Date: ${escapeHtml(file.date)}`);
+                if (file.time) details.push(`Time: ${escapeHtml(file.time)}`);
+                if (file.driver_name) details.push(`Driver: ${escapeHtml(file.driver_name)}`);
+                if (file.device_name) details.push(`Device: ${escapeHtml(file.device_name)}`);
+                if (file.track_name) details.push(`Track: ${escapeHtml(file.track_name)}`);
+                if (file.vehicle_id) details.push(`Vehicle: ${escapeHtml(file.vehicle_id)}`);
+                if (file.event_name) details.push(`Event: ${escapeHtml(file.event_name)}`);
+                if (file.session_name) details.push(`Session: ${escapeHtml(file.session_name)}`);
+                if (file.channel_count) details.push(`Channels: ${file.channel_count}`);
+                if (file.complete === false) details.push('Incomplete upload');
             }
             
+            if (file.parse_error) details.push(`Error: ${escapeHtml(file.parse_error)}`);
+            
             const detailsText = details.length > 0 ? details.join('<br>') : '-';
             
             return `
@@ -1735,7 +1742,11 @@ async function uploadMotecFile() {
                 }
             }
             
-            showMessage(message, 'success');
+            if (data.warning) {
+                showMessage(`${message} - ${data.warning}`, 'error');
+            } else {
+                showMessage(message, 'success');
+            }
             fileInput.value = ''; // Clear input
             const fileNameDisplay = document.getElementById('motec-file-name');
             if (fileNameDisplay) {
@@ -0,0 +1,547 @@
+"""
+Test MoTeC LD/LDX file parsing
+"""
+import struct
+import pytest
+from internal.motec_parser import (
+    MotecParser, MotecLdParser, LdBadSignatureError,
+    LD_MAGIC, LD_MIN_HEADER_SIZE, LD_CHANNEL_META_SIZE,
+)
+
+def make_ld_header(size=2048):
+    """Build a zeroed LD header with the format marker set"""
+    header = bytearray(size)
+    header[:len(LD_MAGIC)] = LD_MAGIC
+    return header
+
+def test_ld_magic_matches_format_marker():
+    """Test the LD magic is the little-endian u32 0x40 marker"""
+    assert struct.unpack('<I', LD_MAGIC)[0] == 0x40
+    assert bytes(make_ld_header()).startswith(LD_MAGIC)
+
+def test_ld_device_info_parsed(tmp_path):
+    """Test logger serial, type and firmware are read from the header"""
+    header = make_ld_header()
+    struct.pack_into('<I', header, 0x46, 12345)
+    header[0x4A:0x4A + 4] = b"ADL3"
+    struct.pack_into('<H', header, 0x52, 420)
+    ld_file = tmp_path / "session.ld"
+    ld_file.write_bytes(bytes(header))
+    
+    metadata = MotecParser.parse_metadata(ld_file)
+    assert metadata["device_serial"] == "12345"
+    assert metadata["device_type"] == "ADL3"
+    assert metadata["firmware_version"] == "420"
+
+def test_ld_device_info_empty_fields_omitted():
+    """Test zeroed device fields are treated as absent"""
+    info = MotecLdParser._parse_device_info(bytes(make_ld_header()))
+    assert info == {}
+
+def test_ldx_version_in_metadata(tmp_path):
+    """Test the LDX root Version attribute is kept in stored metadata"""
+    ldx_file = tmp_path / "workspace.ldx"
+    ldx_file.write_text('<?xml version="1.0"?><LDXFile Version="1.6" Locale="C"><Layers/></LDXFile>')
+    
+    metadata = MotecParser.parse_metadata(ldx_file)
+    assert metadata["ldx_version"] == "1.6"
+
+def test_ldx_version_absent_not_stored(tmp_path):
+    """Test a missing Version attribute is left out of metadata"""
+    ldx_file = tmp_path / "workspace.ldx"
+    ldx_file.write_text('<LDXFile><Layers/></LDXFile>')
+    
+    metadata = MotecParser.parse_metadata(ldx_file)
+    assert "ldx_version" not in metadata
+
+def test_scan_directory_collects_metadata(tmp_path):
+    """Test directory scan returns metadata for each MoTeC file"""
+    (tmp_path / "a.ld").write_bytes(bytes(make_ld_header()))
+    (tmp_path / "b.ldx").write_text('<LDXFile Version="1.6"><Layers/></LDXFile>')
+    (tmp_path / "notes.txt").write_text("not a motec file")
+    nested = tmp_path / "day2"
+    nested.mkdir()
+    (nested / "c.ld").write_bytes(bytes(make_ld_header()))
+    
+    results = MotecParser.scan_directory(tmp_path)
+    assert [r["filename"] for r in results] == ["a.ld", "b.ldx"]
+    
+    results = MotecParser.scan_directory(tmp_path, recursive=True)
+    assert [r["filename"] for r in results] == ["a.ld", "b.ldx", "c.ld"]
+
+def test_scan_directory_keeps_going_on_bad_file(tmp_path):
+    """Test a broken file is reported without aborting the scan"""
+    (tmp_path / "broken.ldx").write_text("<LDXFile><unclosed>")
+    (tmp_path / "good.ld").write_bytes(bytes(make_ld_header()))
+    
+    results = MotecParser.scan_directory(tmp_path)
+    assert len(results) == 2
+    assert "parse_error" in results[0]
+    assert "parse_error" not in results[1]
+
+LDX_WITH_COMMENTS = """<?xml version="1.0"?>
+<!-- Car 3 baseline -->
+<LDXFile Version="1.6">
+ <Layers>
+  <!-- tuned after Pomona test day -->
+  <Details><String Id="Fastest Time" Value="1:02.5"/></Details>
+ </Layers>
+</LDXFile>
+"""
+
+def test_ldx_comments_parsed(tmp_path):
+    """Test LDX comments are returned by the parser"""
+    ldx_file = tmp_path / "workspace.ldx"
+    ldx_file.write_text(LDX_WITH_COMMENTS)
+    
+    parsed = MotecParser.parse_file(ldx_file)
+    assert parsed["comments"] == [" Car 3 baseline ", " tuned after Pomona test day "]
+    assert parsed["details"] == {"Fastest Time": "1:02.5"}
+
+def test_ldx_comments_survive_update(tmp_path):
+    """Test updating a parameter keeps comments in the rewritten file"""
+    from internal.motec_ldx_updater import MotecLdxUpdater
+    ldx_file = tmp_path / "workspace.ldx"
+    ldx_file.write_text(LDX_WITH_COMMENTS)
+    
+    assert MotecLdxUpdater.update_parameter_in_ldx(ldx_file, "ldx_details_Fastest_Time", "1:01.9")
+    
+    parsed = MotecParser.parse_file(ldx_file)
+    assert parsed["details"]["Fastest Time"] == "1:01.9"
+    assert parsed["comments"] == [" Car 3 baseline ", " tuned after Pomona test day "]
+
+def test_detect_file_type(tmp_path):
+    """Test file type detection uses the LD marker and XML start, not size"""
+    ld_file = tmp_path / "a.bin"
+    ld_file.write_bytes(bytes(make_ld_header(16)))
+    ldx_file = tmp_path / "b.bin"
+    ldx_file.write_bytes(b'\xef\xbb\xbf\n<?xml version="1.0"?><LDXFile/>')
+    blob_file = tmp_path / "c.bin"
+    blob_file.write_bytes(b"\x89PNG" + bytes(4096))
+    
+    assert MotecParser.detect_file_type(ld_file) == "ld"
+    assert MotecParser.detect_file_type(ldx_file) == "ldx"
+    assert MotecParser.detect_file_type(blob_file) == "unknown"
+
+def test_parse_file_falls_back_to_detection(tmp_path):
+    """Test files with an unknown extension are parsed by content"""
+    ld_file = tmp_path / "session.dat"
+    ld_file.write_bytes(bytes(make_ld_header()))
+    assert MotecParser.parse_file(ld_file)["file_type"] == "ld"
+    
+    blob_file = tmp_path / "image.dat"
+    blob_file.write_bytes(b"\x89PNG" + bytes(4096))
+    with pytest.raises(ValueError):
+        MotecParser.parse_file(blob_file)
+
+def test_ld_bad_signature_error(tmp_path):
+    """Test a .ld file without the LD marker reports a bad signature"""
+    header = bytearray(2048)
+    struct.pack_into('<I', header, 0x46, 12345)
+    ld_file = tmp_path / "renamed.ld"
+    ld_file.write_bytes(bytes(header))
+    
+    parsed = MotecParser.parse_file(ld_file)
+    assert parsed["error_type"] == "LdBadSignatureError"
+    assert "device_serial" not in parsed
+
+def test_ld_too_small_error(tmp_path):
+    """Test truncated LD files are told apart from non-LD files"""
+    ld_file = tmp_path / "truncated.ld"
+    ld_file.write_bytes(bytes(make_ld_header(512)))
+    assert MotecParser.parse_file(ld_file)["error_type"] == "LdFileTooSmallError"
+    
+    ld_file.write_bytes(LD_MAGIC[:2])
+    assert MotecParser.parse_file(ld_file)["error_type"] == "LdFileTooSmallError"
+
+def test_ld_error_type_in_metadata(tmp_path):
+    """Test stored metadata and directory scans keep the LD error type"""
+    (tmp_path / "renamed.ld").write_bytes(bytes(2048))
+    (tmp_path / "truncated.ld").write_bytes(bytes(make_ld_header(512)))
+    
+    results = MotecParser.scan_directory(tmp_path)
+    assert [r["error_type"] for r in results] == ["LdBadSignatureError", "LdFileTooSmallError"]
+    
+    metadata = MotecParser.parse_metadata(tmp_path / "truncated.ld")
+    assert metadata["error_type"] == "LdFileTooSmallError"
+
+def put_string(buffer, offset, text):
+    """Write a null-padded string into a header buffer"""
+    raw = text.encode("ascii")
+    buffer[offset:offset + len(raw)] = raw
+
+def test_ld_session_fields_parsed(tmp_path):
+    """Test driver, vehicle, venue, event and session come from fixed offsets"""
+    header = make_ld_header(LD_MIN_HEADER_SIZE + 0x482)
+    put_string(header, 0x9E, "Jane Doe")
+    put_string(header, 0xDE, "SC23")
+    put_string(header, 0x15E, "Pomona Fairplex")
+    put_string(header, 0x624, "Baseline setup")
+    struct.pack_into('<I', header, 0x24, LD_MIN_HEADER_SIZE)
+    put_string(header, LD_MIN_HEADER_SIZE, "Autocross Round 2")
+    put_string(header, LD_MIN_HEADER_SIZE + 64, "Practice 1")
+    put_string(header, LD_MIN_HEADER_SIZE + 128, "Dry, 24C track temp")
+    ld_file = tmp_path / "session.ld"
+    ld_file.write_bytes(bytes(header))
+    
+    metadata = MotecParser.parse_metadata(ld_file)
+    assert metadata["driver_name"] == "Jane Doe"
+    assert metadata["vehicle_id"] == "SC23"
+    assert metadata["track_name"] == "Pomona Fairplex"
+    assert metadata["comment"] == "Baseline setup"
+    assert metadata["event_name"] == "Autocross Round 2"
+    assert metadata["session_name"] == "Practice 1"
+    assert metadata["event_comment"] == "Dry, 24C track temp"
+
+def test_ld_event_pointer_out_of_range_ignored(tmp_path):
+    """Test an event pointer past the end of the file is skipped"""
+    header = make_ld_header()
+    struct.pack_into('<I', header, 0x24, 0xFFFFFF)
+    ld_file = tmp_path / "short.ld"
+    ld_file.write_bytes(bytes(header))
+    
+    parsed = MotecParser.parse_file(ld_file)
+    assert "parse_error" not in parsed
+    assert "event_name" not in parsed
+
+def test_ld_start_time_parsed(tmp_path):
+    """Test header date and time fields become an ISO start time"""
+    header = make_ld_header()
+    put_string(header, 0x5E, "14/09/2026")
+    put_string(header, 0x7E, "13:45:07")
+    ld_file = tmp_path / "session.ld"
+    ld_file.write_bytes(bytes(header))
+    
+    metadata = MotecParser.parse_metadata(ld_file)
+    assert metadata["date"] == "14/09/2026"
+    assert metadata["time"] == "13:45:07"
+    assert metadata["start_time"] == "2026-09-14T13:45:07"
+
+def test_ld_blank_start_time_absent():
+    """Test zeroed date/time fields do not produce a start time"""
+    assert MotecLdParser._parse_start_time(bytes(make_ld_header())) == {}
+    
+    header = make_ld_header()
+    put_string(header, 0x5E, "99/99/2026")
+    put_string(header, 0x7E, "13:45:07")
+    info = MotecLdParser._parse_start_time(bytes(header))
+    assert "start_time" not in info
+    assert info["date"] == "99/99/2026"
+
+NAMESPACED_LDX = """<?xml version="1.0"?>
+<i2:LDXFile xmlns:i2="urn:motec:i2" i2:Version="1.7">
+ <i2:Layers><i2:Details><i2:String i2:Id="Total Laps" Value="12"/></i2:Details></i2:Layers>
+ <i2:Maths><i2:MathItems><i2:MathScaleOffset Id="Brake Bias" Scale="1" Offset="0"/></i2:MathItems></i2:Maths>
+ <i2:Descriptors><i2:Descriptor Id="OilTemp" DisplayDPS="1"/></i2:Descriptors>
+</i2:LDXFile>
+"""
+
+def test_ldx_namespaced_import_then_update(tmp_path):
+    """Test a namespaced LDX imports every section and accepts write-backs"""
+    from internal.motec_translator import MotecTranslator
+    from internal.motec_ldx_updater import MotecLdxUpdater
+    ldx_file = tmp_path / "workspace.ldx"
+    ldx_file.write_text(NAMESPACED_LDX)
+    
+    names = {p["parameter_name"] for p in MotecTranslator.ldx_to_parameters(ldx_file, subteam="Vehicle Dynamics")}
+    assert {"ldx_details_Total_Laps", "ldx_math_Brake_Bias_scale", "ldx_desc_OilTemp_dps"} <= names
+    
+    assert MotecLdxUpdater.update_parameter_in_ldx(ldx_file, "ldx_details_Total_Laps", "14")
+    assert MotecLdxUpdater.update_parameter_in_ldx(ldx_file, "ldx_math_Brake_Bias_scale", "1.2")
+    assert MotecLdxUpdater.update_parameter_in_ldx(ldx_file, "ldx_desc_OilTemp_dps", "2")
+    
+    parsed = MotecParser.parse_file(ldx_file)
+    assert parsed["details"]["Total Laps"] == "14"
+    assert parsed["version"] == "1.7"
+    assert 'xmlns:i2="urn:motec:i2"' in ldx_file.read_text()
+
+def test_ld_start_time_rejects_markup():
+    """Test date/time fields that are not digits and separators are dropped"""
+    header = make_ld_header()
+    put_string(header, 0x5E, "<svg/onload=f()>")
+    put_string(header, 0x7E, "13:45:07")
+    info = MotecLdParser._parse_start_time(bytes(header))
+    assert "date" not in info
+    assert "start_time" not in info
+    assert info["time"] == "13:45:07"
+
+def test_ldx_namespaced_and_alternate_root(tmp_path):
+    """Test namespaced elements/attributes and alternate root casing parse"""
+    ldx_file = tmp_path / "workspace.ldx"
+    ldx_file.write_text(
+        '<i2:workspace xmlns:i2="urn:motec:i2" i2:Version="1.7">'
+        '<i2:Layers><i2:Details><i2:String i2:Id="Total Laps" Value="12"/></i2:Details></i2:Layers>'
+        '</i2:workspace>'
+    )
+    
+    parsed = MotecParser.parse_file(ldx_file)
+    assert "parse_error" not in parsed
+    assert parsed["version"] == "1.7"
+    assert parsed["details"] == {"Total Laps": "12"}
+
+def test_ldx_unknown_root_named_in_error(tmp_path):
+    """Test an unrecognised root element is named in the parse error"""
+    ldx_file = tmp_path / "other.ldx"
+    ldx_file.write_text('<Project><Layers/></Project>')
+    
+    parsed = MotecParser.parse_file(ldx_file)
+    assert "<Project>" in parsed["parse_error"]
+
+def put_channel_meta(buffer, offset, next_ptr, name, units, rate, count, type_code=0x03, width=2, data_ptr=0):
+    """Write one LD channel meta record into a buffer"""
+    struct.pack_into('<IIII', buffer, offset, 0, next_ptr, data_ptr, count)
+    struct.pack_into('<HHH', buffer, offset + 0x12, type_code, width, rate)
+    put_string(buffer, offset + 0x20, name)
+    put_string(buffer, offset + 0x48, units)
+
+def test_ld_channel_meta_parsed(tmp_path):
+    """Test channel names, units, rates, counts and types come from the meta list"""
+    first, second = 0x800, 0x800 + LD_CHANNEL_META_SIZE
+    header = make_ld_header(second + LD_CHANNEL_META_SIZE)
+    struct.pack_into('<I', header, 0x08, first)
+    put_channel_meta(header, first, second, "Engine RPM", "rpm", 100, 6000)
+    put_channel_meta(header, second, 0, "Wheel Speed FL", "km/h", 1000, 60000, type_code=0x07, width=4)
+    ld_file = tmp_path / "session.ld"
+    ld_file.write_bytes(bytes(header))
+    
+    metadata = MotecParser.parse_metadata(ld_file)
+    assert metadata["channel_count"] == 2
+    assert metadata["channels"][0] == {
+        "name": "Engine RPM", "short_name": "", "units": "rpm",
+        "sample_rate": 100, "sample_count": 6000, "data_type": "int16", "data_size": 12000,
+    }
+    assert metadata["channels"][1]["sample_rate"] == 1000
+    assert metadata["channels"][1]["data_type"] == "float32"
+    assert metadata["consistent_rate"] is False
+    assert metadata["channel_units"] == ["km/h", "rpm"]
+
+def test_ld_channel_meta_loop_and_limit(tmp_path):
+    """Test a self-referencing meta list terminates and the channel cap applies"""
+    first = 0x800
+    header = make_ld_header(first + LD_CHANNEL_META_SIZE)
+    struct.pack_into('<I', header, 0x08, first)
+    put_channel_meta(header, first, first, "Loop", "", 10, 1)
+    ld_file = tmp_path / "loop.ld"
+    ld_file.write_bytes(bytes(header))
+    parsed = MotecParser.parse_file(ld_file)
+    assert parsed["channel_count"] == 1
+    assert parsed["consistent_rate"] is True
+    
+    with open(ld_file, 'rb') as f:
+        info = MotecLdParser._parse_channel_meta(f, first, len(header), 0)
+    assert info["channel_count"] == 0
+    assert info["channels_truncated"] is True
+
+def test_ld_is_complete_detects_truncation(tmp_path):
+    """Test declared sample blocks past the end of the file mark it incomplete"""
+    meta, data = 0x800, 0x800 + LD_CHANNEL_META_SIZE
+    header = make_ld_header(data + 100 * 2)
+    struct.pack_into('<I', header, 0x08, meta)
+    put_channel_meta(header, meta, 0, "Engine RPM", "rpm", 100, 100, data_ptr=data)
+    ld_file = tmp_path / "session.ld"
+    ld_file.write_bytes(bytes(header))
+    assert MotecLdParser.is_complete(ld_file) is True
+    assert MotecParser.parse_metadata(ld_file)["complete"] is True
+    
+    ld_file.write_bytes(bytes(header[:data + 50]))
+    assert MotecLdParser.is_complete(ld_file) is False
+    assert MotecParser.parse_metadata(ld_file)["complete"] is False
+    
+    ld_file.write_bytes(bytes(header[:512]))
+    assert MotecLdParser.is_complete(ld_file) is False
+    
+    ld_file.write_bytes(bytes(2048))
+    with pytest.raises(LdBadSignatureError):
+        MotecLdParser.is_complete(ld_file)
+
+def test_ld_partial_fields_kept_on_error(tmp_path, monkeypatch):
+    """Test fields read before a failure are returned with the error"""
+    header = make_ld_header()
+    put_string(header, 0x9E, "Jane Doe")
+    ld_file = tmp_path / "session.ld"
+    ld_file.write_bytes(bytes(header))
+    
+    def fail(*args):
+        raise OSError("read failed")
+    monkeypatch.setattr(MotecLdParser, "_parse_channel_meta", staticmethod(fail))
+    parsed = MotecParser.parse_file(ld_file)
+    
+    assert parsed["error_type"] == "OSError"
+    assert parsed["driver_name"] == "Jane Doe"
+
+def test_gzip_ld_parsed_transparently(tmp_path):
+    """Test .ld.gz archives are detected, decompressed and scanned"""
+    import gzip
+    header = make_ld_header()
+    put_string(header, 0x9E, "Jane Doe")
+    gz_file = tmp_path / "session.ld.gz"
+    gz_file.write_bytes(gzip.compress(bytes(header)))
+    
+    assert MotecParser.detect_file_type(gz_file) == "ld"
+    parsed = MotecParser.parse_file(gz_file)
+    assert parsed["file_type"] == "ld"
+    assert parsed["filename"] == "session.ld.gz"
+    assert parsed["driver_name"] == "Jane Doe"
+    assert parsed["file_size"] == len(header)
+    
+    results = MotecParser.scan_directory(tmp_path)
+    assert [r["filename"] for r in results] == ["session.ld.gz"]
+    assert results[0]["compressed_size"] == gz_file.stat().st_size
+
+def test_reindex_refreshes_stored_metadata(tmp_path, monkeypatch):
+    """Test reindex re-parses uploads and keeps their id and upload time"""
+    from internal import motec_file_manager as manager
+    monkeypatch.setattr(manager, "BASE_DIR", tmp_path)
+    monkeypatch.setattr(manager, "MOTEC_FILES_DIR", tmp_path / "data" / "motec_files")
+    monkeypatch.setattr(manager, "MOTEC_METADATA_FILE", tmp_path / "data" / "motec_files_metadata.json")
+    
+    (manager.MOTEC_FILES_DIR / "ld").mkdir(parents=True)
+    header = make_ld_header()
+    put_string(header, 0xDE, "SC23")
+    (manager.MOTEC_FILES_DIR / "ld" / "session.ld").write_bytes(bytes(header))
+    (manager.MOTEC_FILES_DIR / "ld" / "stray.ld").write_bytes(bytes(header))
+    manager.save_metadata([{
+        "id": "1_session.ld", "uploaded_at": "2026-09-14T13:45:07",
+        "file_path": "data/motec_files/ld/session.ld", "filename": "session.ld",
+    }])
+    
+    assert manager.reindex_files() == {"scanned": 2, "updated": 1}
+    entries = manager.load_metadata()
+    assert len(entries) == 1
+    assert entries[0]["vehicle_id"] == "SC23"
+    assert entries[0]["id"] == "1_session.ld"
+    assert entries[0]["uploaded_at"] == "2026-09-14T13:45:07"
+
+def test_ld_small_header_size_keeps_fixed_fields(tmp_path):
+    """Test a configured header size below the fixed header still reads the comment"""
+    header = make_ld_header()
+    put_string(header, 0x624, "Baseline setup")
+    ld_file = tmp_path / "session.ld"
+    ld_file.write_bytes(bytes(header))
+    
+    parsed = MotecLdParser.parse(ld_file, header_size=512)
+    assert parsed["comment"] == "Baseline setup"
+    assert parsed["header_signature"] == ["0x40", "0x0", "0x0", "0x0"]
+
+def test_ldx_namespaced_attribute_does_not_clobber_plain(tmp_path):
+    """Test a plain attribute survives a namespaced one of the same local name"""
+    import xml.etree.ElementTree as ET
+    from internal.motec_ldx_updater import MotecLdxUpdater
+    ldx_file = tmp_path / "workspace.ldx"
+    ldx_file.write_text(
+        '<LDXFile xmlns:i2="urn:motec:i2" Version="1.6" i2:Version="2">'
+        '<Layers><Details><String Id="Total Laps" Value="12"/></Details></Layers>'
+        '</LDXFile>'
+    )
+    assert MotecParser.parse_file(ldx_file)["version"] == "1.6"
+    
+    assert MotecLdxUpdater.update_parameter_in_ldx(ldx_file, "ldx_details_Total_Laps", "14")
+    text = ldx_file.read_text()
+    assert 'Version="1.6"' in text
+    assert 'i2:Version="2"' in text
+    assert "urn:motec:i2" not in ET._namespace_map
+
+def test_gzip_only_for_gz_names(tmp_path):
+    """Test gzip content under a plain .ld name is not decompressed"""
+    import gzip
+    ld_file = tmp_path / "upload.ld"
+    ld_file.write_bytes(gzip.compress(bytes(make_ld_header())))
+    
+    assert MotecParser.detect_file_type(ld_file) == "unknown"
+    assert MotecParser.parse_file(ld_file)["error_type"] == "LdBadSignatureError"
+
+def test_gzip_decompressed_size_capped(tmp_path, monkeypatch):
+    """Test a .gz archive that expands past the limit is rejected"""
+    import gzip
+    from internal.config.settings import settings
+    from internal.motec_parser import GzipTooLargeError
+    monkeypatch.setattr(settings, "MOTEC_GZIP_MAX_SIZE", 4096)
+    gz_file = tmp_path / "bomb.ld.gz"
+    gz_file.write_bytes(gzip.compress(bytes(make_ld_header(1024 * 1024))))
+    
+    with pytest.raises(GzipTooLargeError):
+        MotecParser.parse_file(gz_file)
+    with pytest.raises(GzipTooLargeError):
+        MotecLdParser.is_complete(gz_file)
+    assert MotecParser.scan_directory(tmp_path)[0]["error_type"] == "GzipTooLargeError"
+
+def test_gzip_ldx_import_and_update(tmp_path):
+    """Test .ldx.gz files import every section, update in place and stay compressed"""
+    import gzip
+    from internal.motec_translator import MotecTranslator
+    from internal.motec_ldx_updater import MotecLdxUpdater
+    gz_file = tmp_path / "workspace.ldx.gz"
+    gz_file.write_bytes(gzip.compress(NAMESPACED_LDX.encode()))
+    
+    names = {p["parameter_name"] for p in MotecTranslator.ldx_to_parameters(gz_file)}
+    assert {"ldx_details_Total_Laps", "ldx_math_Brake_Bias_scale", "ldx_desc_OilTemp_dps"} <= names
+    
+    assert MotecLdxUpdater.update_parameter_in_ldx(gz_file, "ldx_math_Brake_Bias_scale", "1.2")
+    assert gz_file.read_bytes().startswith(b"\x1f\x8b")
+    assert b'Scale="1.2"' in gzip.decompress(gz_file.read_bytes())
+
+def test_gzip_ld_is_complete(tmp_path):
+    """Test completeness is checked on the decompressed .ld.gz contents"""
+    import gzip
+    gz_file = tmp_path / "session.ld.gz"
+    gz_file.write_bytes(gzip.compress(bytes(make_ld_header())))
+    assert MotecLdParser.is_complete(gz_file) is True
+
+def test_ld_channel_limit_reported_as_error(tmp_path, monkeypatch):
+    """Test exceeding the channel cap reports the limit and the declared count"""
+    from internal.config.settings import settings
+    monkeypatch.setattr(settings, "MOTEC_LD_MAX_CHANNELS", 2)
+    first = 0x800
+    header = make_ld_header(first + 3 * LD_CHANNEL_META_SIZE)
+    struct.pack_into('<I', header, 0x08, first)
+    for i in range(3):
+        offset = first + i * LD_CHANNEL_META_SIZE
+        next_ptr = offset + LD_CHANNEL_META_SIZE if i < 2 else 0
+        put_channel_meta(header, offset, next_ptr, f"Channel {i}", "", 10, 1)
+    ld_file = tmp_path / "session.ld"
+    ld_file.write_bytes(bytes(header))
+    
+    metadata = MotecParser.parse_metadata(ld_file)
+    assert metadata["error_type"] == "LdTooManyChannelsError"
+    assert "3 channels" in metadata["parse_error"] and "limit of 2" in metadata["parse_error"]
+    assert metadata["channel_count"] == 2
+    assert metadata["declared_channel_count"] == 3
+
+def test_ldx_channel_units_collected(tmp_path):
+    """Test LDX math item and descriptor units are listed once each"""
+    ldx_file = tmp_path / "workspace.ldx"
+    ldx_file.write_text(
+        '<LDXFile><Maths><MathItems>'
+        '<MathScaleOffset Id="Speed" Unit="km/h"/><MathScaleOffset Id="Gain" Unit=""/>'
+        '</MathItems></Maths><Descriptors>'
+        '<Descriptor Id="Wheel Speed" DisplayUnit="kph"/><Descriptor Id="Speed" DisplayUnit="km/h"/>'
+        '</Descriptors></LDXFile>'
+    )
+    assert MotecParser.parse_metadata(ldx_file)["channel_units"] == ["km/h", "kph"]
+
+def test_ld_channels_by_rate():
+    """Test channels are listed highest sample rate first"""
+    channels = [
+        {"name": "Oil Temp", "sample_rate": 10},
+        {"name": "Wheel Speed FL", "sample_rate": 1000},
+        {"name": "Engine RPM", "sample_rate": 100},
+    ]
+    assert MotecLdParser.channels_by_rate(channels) == [
+        ("Wheel Speed FL", 1000), ("Engine RPM", 100), ("Oil Temp", 10),
+    ]
+
+def test_ld_size_breakdown_sums_to_data_block(tmp_path):
+    """Test per-channel sizes come from count x width and cover the data block"""
+    meta = 0x800
+    data = meta + 2 * LD_CHANNEL_META_SIZE
+    header = make_ld_header(data + 100 * 2 + 60 * 4)
+    struct.pack_into('<I', header, 0x08, meta)
+    put_channel_meta(header, meta, meta + LD_CHANNEL_META_SIZE, "Engine RPM", "rpm", 100, 100, data_ptr=data)
+    put_channel_meta(header, meta + LD_CHANNEL_META_SIZE, 0, "Lambda", "", 50, 60,
+                     type_code=0x07, width=4, data_ptr=data + 200)
+    ld_file = tmp_path / "session.ld"
+    ld_file.write_bytes(bytes(header))
+    
+    breakdown = MotecLdParser.size_breakdown(MotecParser.parse_file(ld_file)["channels"])
+    assert breakdown == [("Lambda", 240), ("Engine RPM", 200)]
+    assert sum(size for _, size in breakdown) == len(header) - data